@@ -13,14 +13,19 @@ use std::{
     cell::RefCell,
     collections::HashMap,
     ffi::{CStr, CString},
+    os::raw::c_char,
+    slice::from_raw_parts,
+    str::from_utf8,
 };
+#[cfg(feature = "global-interner")]
+use std::sync::{OnceLock, RwLock};
 
-struct RawCStrs(RefCell<HashMap<String, *mut i8>>);
+struct RawCStrs(RefCell<HashMap<String, *mut c_char>>);
 
 impl Drop for RawCStrs {
     fn drop(&mut self) {
         self.0.borrow_mut().iter_mut().for_each(|(_, c)| unsafe {
-            drop(CString::from_raw((*c) as *mut u8));
+            drop(CString::from_raw(*c));
         });
         self.0.borrow_mut().clear();
     }
@@ -30,7 +35,7 @@ thread_local! {
     static RAW_CSTRS: RawCStrs = RawCStrs(RefCell::new(HashMap::new()));
 }
 
-/// Create a constant raw C string as a `*mut i8` from a Rust string reference. C Strings are cached,
+/// Create a constant raw C string as a `*mut c_char` from a Rust string reference. C Strings are cached,
 /// and creating the same string twice will cost zero additional memory. This is useful when calling
 /// C APIs that take a string as an argument, particularly when the string can't be known at compile
 /// time, although this function is also efficient in space (but not time) when a constant string
@@ -43,7 +48,7 @@ thread_local! {
 ///   [`CStr::from_ptr`] instead, and convert to a string with
 ///   `.to_str().expect("...").to_owned()` instead.
 ///
-pub fn raw_cstr<S>(str: S) -> Result<*mut i8>
+pub fn raw_cstr<S>(str: S) -> Result<*mut c_char>
 where
     S: AsRef<str>,
 {
@@ -54,66 +59,415 @@ where
         if let Some(saved) = saved {
             Ok(*saved)
         } else {
-            let raw = CString::new(str.as_ref())?.into_raw() as *mut i8;
+            let raw = CString::new(str.as_ref())?.into_raw();
             raw_cstrs_map.insert(str.as_ref().to_string(), raw);
             Ok(raw)
         }
     })
 }
 
+/// Evict and free a single string from the current thread's [`raw_cstr`] cache,
+/// returning whether it was present. This lets long-lived worker threads that
+/// generate many distinct strings reclaim memory without waiting for the thread
+/// to exit.
+///
+/// # Safety
+///
+/// - Callers must not pass a pointer previously returned by [`raw_cstr`] for `s`
+///   to C after calling this function, since it is freed the same way as the
+///   per-thread cache's own [`Drop`] implementation.
+pub fn free_cstr(s: &str) -> bool {
+    RAW_CSTRS.with(|rc| {
+        let mut raw_cstrs_map = rc.0.borrow_mut();
+
+        match raw_cstrs_map.remove(s) {
+            Some(raw) => {
+                drop(unsafe { CString::from_raw(raw) });
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Evict and free every string in the current thread's [`raw_cstr`] cache.
+///
+/// # Safety
+///
+/// - Callers must not pass any pointer previously returned by [`raw_cstr`] on this
+///   thread to C after calling this function, since each is freed the same way as
+///   the per-thread cache's own [`Drop`] implementation.
+pub fn clear_cstrs() {
+    RAW_CSTRS.with(|rc| {
+        rc.0.borrow_mut().drain().for_each(|(_, raw)| {
+            drop(unsafe { CString::from_raw(raw) });
+        });
+    });
+}
+
+/// Return `(count, total_bytes)` for the current thread's [`raw_cstr`] cache,
+/// where `total_bytes` includes each cached string's NUL terminator. Useful for
+/// servers that want to track and bound per-thread cache growth.
+pub fn cstr_cache_stats() -> (usize, usize) {
+    RAW_CSTRS.with(|rc| {
+        let raw_cstrs_map = rc.0.borrow();
+        let count = raw_cstrs_map.len();
+        let total_bytes = raw_cstrs_map.keys().map(|s| s.len() + 1).sum();
+
+        (count, total_bytes)
+    })
+}
+
+#[cfg(feature = "global-interner")]
+fn global_cstrs() -> &'static RwLock<HashMap<String, usize>> {
+    static GLOBAL_CSTRS: OnceLock<RwLock<HashMap<String, usize>>> = OnceLock::new();
+    GLOBAL_CSTRS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Create a constant raw C string as a `*mut c_char` from a Rust string reference,
+/// interned in a process-global cache shared across all threads, unlike
+/// [`raw_cstr`]'s thread-local cache. Creating the same string from any thread
+/// costs zero additional memory after the first call from any thread.
+///
+/// Because a globally-interned pointer must remain valid for every thread for the
+/// rest of the process's life, the backing [`CString`] is intentionally leaked
+/// (via [`Box::leak`]) rather than freed, so there is no `Drop`-based reclamation
+/// and no equivalent of [`free_cstr`] or [`clear_cstrs`]. Only use this for
+/// strings that are genuinely long-lived, such as constants shared across a
+/// thread pool.
+#[cfg(feature = "global-interner")]
+pub fn raw_cstr_global<S>(str: S) -> Result<*mut c_char>
+where
+    S: AsRef<str>,
+{
+    if let Some(raw) = global_cstrs()
+        .read()
+        .expect("global cstr cache lock poisoned")
+        .get(str.as_ref())
+    {
+        return Ok(*raw as *mut c_char);
+    }
+
+    let mut global_cstrs_map = global_cstrs().write().expect("global cstr cache lock poisoned");
+
+    // Another thread may have interned the same string while we were waiting for the write lock.
+    if let Some(raw) = global_cstrs_map.get(str.as_ref()) {
+        return Ok(*raw as *mut c_char);
+    }
+
+    let leaked: &'static CStr = Box::leak(CString::new(str.as_ref())?.into_boxed_c_str());
+    let raw = leaked.as_ptr() as *mut c_char;
+    global_cstrs_map.insert(str.as_ref().to_string(), raw as usize);
+    Ok(raw)
+}
+
 /// A type that can be converted to a raw C string
 pub trait AsRawCstr {
     /// Get a type as a raw C string
-    fn as_raw_cstr(&self) -> Result<*mut i8>;
+    fn as_raw_cstr(&self) -> Result<*mut c_char>;
 }
 
 impl AsRawCstr for &'static [u8] {
     /// Get a static slice as a raw C string. Useful for interfaces.
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         if self.last().is_some_and(|l| *l == 0) {
-            Ok(self.as_ptr() as *const i8 as *mut i8)
+            Ok(self.as_ptr() as *const c_char as *mut c_char)
         } else {
             bail!("Empty slice or last element is nonzero: {:?}", self);
         }
     }
 }
 
-impl AsRawCstr for *mut i8 {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+impl AsRawCstr for *mut c_char {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         Ok(*self)
     }
 }
 
 impl AsRawCstr for &str {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         raw_cstr(self)
     }
 }
 
 impl AsRawCstr for String {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         raw_cstr(self)
     }
 }
 
 impl AsRawCstr for CString {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         // Make a copy of the string so that we can return a pointer to it
         raw_cstr(self.to_str()?)
     }
 }
 
 impl AsRawCstr for CStr {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
         // Make a copy of the string so that we can return a pointer to it
         raw_cstr(self.to_str()?)
     }
 }
 
 impl AsRawCstr for &'static CStr {
-    fn as_raw_cstr(&self) -> Result<*mut i8> {
+    fn as_raw_cstr(&self) -> Result<*mut c_char> {
+        // No need to copy for static lifetime CStrs because the pointer
+        // lifetime is also static
+        Ok(self.as_ptr() as *mut c_char)
+    }
+}
+
+/// A type that can be converted to a raw C string interned in the process-global
+/// cache (see [`raw_cstr_global`]) instead of the thread-local one used by
+/// [`AsRawCstr`].
+#[cfg(feature = "global-interner")]
+pub trait AsRawCstrGlobal {
+    /// Get a type as a raw C string, interned process-globally
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char>;
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for &'static [u8] {
+    /// Get a static slice as a raw C string. Useful for interfaces.
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        self.as_raw_cstr()
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for *mut c_char {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        Ok(*self)
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for &str {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        raw_cstr_global(self)
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for String {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        raw_cstr_global(self)
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for CString {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        // Make a copy of the string so that we can return a pointer to it
+        raw_cstr_global(self.to_str()?)
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for CStr {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
+        // Make a copy of the string so that we can return a pointer to it
+        raw_cstr_global(self.to_str()?)
+    }
+}
+
+#[cfg(feature = "global-interner")]
+impl AsRawCstrGlobal for &'static CStr {
+    fn as_raw_cstr_global(&self) -> Result<*mut c_char> {
         // No need to copy for static lifetime CStrs because the pointer
         // lifetime is also static
-        Ok(self.as_ptr() as *mut i8)
+        self.as_raw_cstr()
+    }
+}
+
+/// A type that can be read back from a raw C string pointer into an owned Rust
+/// [`String`]. This is the inverse of [`AsRawCstr`], for use when a C API hands a
+/// `*const`/`*mut` character pointer back to Rust.
+#[allow(clippy::wrong_self_convention)]
+pub trait FromRawCstr {
+    /// Read a NUL-terminated raw C string into an owned [`String`].
+    ///
+    /// # Safety
+    ///
+    /// - `self` must be a valid pointer to a NUL-terminated string, or the crate's
+    ///   behavior on dereference is undefined, per [`CStr::from_ptr`].
+    unsafe fn from_raw_cstr(self) -> Result<String>;
+
+    /// Like [`FromRawCstr::from_raw_cstr`], but replaces invalid UTF-8 sequences
+    /// with the Unicode replacement character instead of returning an error.
+    ///
+    /// # Safety
+    ///
+    /// See [`FromRawCstr::from_raw_cstr`].
+    unsafe fn from_raw_cstr_lossy(self) -> String;
+
+    /// Read a raw C string of a known length (for example, one returned via an
+    /// out-parameter by a C API) into an owned [`String`], without relying on NUL
+    /// termination.
+    ///
+    /// # Safety
+    ///
+    /// `self` must be valid for reads of `len` bytes.
+    unsafe fn from_raw_cstr_with_len(self, len: usize) -> Result<String>;
+}
+
+impl FromRawCstr for *const i8 {
+    unsafe fn from_raw_cstr(self) -> Result<String> {
+        Ok(CStr::from_ptr(self as *const c_char).to_str()?.to_owned())
+    }
+
+    unsafe fn from_raw_cstr_lossy(self) -> String {
+        CStr::from_ptr(self as *const c_char)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    unsafe fn from_raw_cstr_with_len(self, len: usize) -> Result<String> {
+        Ok(from_utf8(from_raw_parts(self as *const u8, len))?.to_owned())
+    }
+}
+
+impl FromRawCstr for *mut i8 {
+    unsafe fn from_raw_cstr(self) -> Result<String> {
+        (self as *const i8).from_raw_cstr()
+    }
+
+    unsafe fn from_raw_cstr_lossy(self) -> String {
+        (self as *const i8).from_raw_cstr_lossy()
+    }
+
+    unsafe fn from_raw_cstr_with_len(self, len: usize) -> Result<String> {
+        (self as *const i8).from_raw_cstr_with_len(len)
+    }
+}
+
+impl FromRawCstr for *const u8 {
+    /// Reinterprets the bytes as `c_char` without a copy; `c_char` is unsigned on
+    /// some targets (aarch64, ARM, ...), so buffers are often filled as
+    /// `*const u8`.
+    unsafe fn from_raw_cstr(self) -> Result<String> {
+        Ok(CStr::from_ptr(self as *const c_char).to_str()?.to_owned())
+    }
+
+    unsafe fn from_raw_cstr_lossy(self) -> String {
+        CStr::from_ptr(self as *const c_char)
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    unsafe fn from_raw_cstr_with_len(self, len: usize) -> Result<String> {
+        Ok(from_utf8(from_raw_parts(self, len))?.to_owned())
+    }
+}
+
+impl FromRawCstr for *mut u8 {
+    unsafe fn from_raw_cstr(self) -> Result<String> {
+        (self as *const u8).from_raw_cstr()
+    }
+
+    unsafe fn from_raw_cstr_lossy(self) -> String {
+        (self as *const u8).from_raw_cstr_lossy()
+    }
+
+    unsafe fn from_raw_cstr_with_len(self, len: usize) -> Result<String> {
+        (self as *const u8).from_raw_cstr_with_len(len)
+    }
+}
+
+/// An owned raw C string that transfers ownership of its allocation across the FFI
+/// boundary, for C APIs whose contract is "the callee frees the string" (for
+/// example, a registered deallocator or a matching `free`-style function).
+///
+/// Unlike [`raw_cstr`], which caches pointers in a thread-local map and frees them
+/// only on thread exit, an [`OwnedRawCstr`] is never cached: it is built directly
+/// from [`CString::into_raw`], and the caller is responsible for either handing it
+/// to C (via [`OwnedRawCstr::into_raw`]) or reclaiming it (via
+/// [`OwnedRawCstr::from_raw`]).
+///
+/// **Do not** mix [`OwnedRawCstr`] pointers with [`raw_cstr`]'s cached pointers:
+/// passing a cached pointer to [`OwnedRawCstr::from_raw`] (or vice versa) will
+/// cause a double free.
+#[repr(C)]
+pub struct OwnedRawCstr(*mut c_char);
+
+impl OwnedRawCstr {
+    /// Create a new [`OwnedRawCstr`] from a Rust string, allocating via
+    /// [`CString::into_raw`].
+    pub fn new<S>(str: S) -> Result<Self>
+    where
+        S: AsRef<str>,
+    {
+        Ok(Self(CString::new(str.as_ref())?.into_raw() as *mut c_char))
+    }
+
+    /// Borrow the raw pointer without surrendering ownership. The returned pointer
+    /// is only valid as long as `self` is alive.
+    pub fn as_ptr(&self) -> *mut c_char {
+        self.0
+    }
+
+    /// Surrender ownership of the underlying allocation across the FFI boundary.
+    /// After calling this, the caller (typically the C side) is responsible for
+    /// freeing the string, and it will *not* be freed when the [`OwnedRawCstr`]
+    /// would otherwise have been dropped.
+    pub fn into_raw(self) -> *mut c_char {
+        let ptr = self.0;
+        std::mem::forget(self);
+        ptr
+    }
+
+    /// Reclaim an [`OwnedRawCstr`] previously surrendered via
+    /// [`OwnedRawCstr::into_raw`], so that it is dropped (and its allocation freed
+    /// via [`CString::from_raw`]) like any other owned value.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been obtained from [`OwnedRawCstr::into_raw`] (or
+    ///   equivalently, [`CString::into_raw`]), and must not have already been freed.
+    pub unsafe fn from_raw(ptr: *mut c_char) -> Self {
+        Self(ptr)
+    }
+}
+
+impl Drop for OwnedRawCstr {
+    fn drop(&mut self) {
+        drop(unsafe { CString::from_raw(self.0) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compiles and passes regardless of whether `c_char` is signed (`i8`, as on
+    /// x86_64) or unsigned (`u8`, as on aarch64 and ARM), since `raw_cstr` and
+    /// `FromRawCstr` both operate in terms of `c_char` rather than a hardcoded
+    /// signedness.
+    #[test]
+    fn raw_cstr_roundtrips_through_c_char() {
+        let ptr: *mut c_char = raw_cstr("hello").expect("valid C string");
+        let s = unsafe { ptr.from_raw_cstr() }.expect("valid UTF-8");
+        assert_eq!(s, "hello");
+    }
+
+    #[test]
+    fn free_cstr_evicts_and_reports_stats() {
+        clear_cstrs();
+
+        raw_cstr("evict-me").expect("valid C string");
+        assert_eq!(cstr_cache_stats(), (1, "evict-me".len() + 1));
+
+        assert!(free_cstr("evict-me"));
+        assert!(!free_cstr("evict-me"));
+        assert_eq!(cstr_cache_stats(), (0, 0));
+    }
+
+    #[cfg(feature = "global-interner")]
+    #[test]
+    fn raw_cstr_global_interns_across_calls() {
+        let first = raw_cstr_global("shared").expect("valid C string");
+        let second = raw_cstr_global("shared").expect("valid C string");
+        assert_eq!(first, second);
     }
 }